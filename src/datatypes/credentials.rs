@@ -10,6 +10,8 @@
 use crate::datatypes::base58::{Base58, BASE58_STRLEN, INV_LOOKUPTABLE, LOOKUPTABLE};
 use crate::datatypes::voter_ids::Voter_ID;
 use crate::primitives::group::{Point, Scalar};
+use crate::primitives::signature::{self, Signature};
+use rayon::prelude::*;
 use ring::digest;
 use ring::pbkdf2::{self, PBKDF2_HMAC_SHA256};
 use ring::rand::SecureRandom;
@@ -74,21 +76,155 @@ impl Password {
     pub fn validate_checksum(&self) -> bool {
         self.0 .0.as_bytes()[BASE58_STRLEN - 1] == LOOKUPTABLE[self.checksum() as usize]
     }
+
+    /// Checks, in constant time, that this password derives to `expected` under
+    /// `params` and `salt`, without fully expanding into an El Gamal keypair.
+    pub fn verify(&self, salt: &UUID, expected: &[u8], params: &KdfParams) -> bool {
+        let secret = (&self.0).into();
+        let salt: &[u8] = (&salt.0).into();
+        pbkdf2::verify(params.algorithm(), params.iterations(), salt, secret, expected).is_ok()
+    }
+}
+
+/// The key-derivation function, and its parameters, used to turn a [`Password`]
+/// into an El Gamal secret key. Carried alongside each [`Credential`] so that
+/// iteration counts can be strengthened over time without breaking credentials
+/// already derived at a weaker setting: a credential simply remembers the
+/// parameters it was derived with.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum KdfParams {
+    Pbkdf2Sha256 { iterations: NonZeroU32 },
+}
+
+impl KdfParams {
+    /// Iteration count recommended by current password-hashing guidance; used
+    /// whenever a caller does not ask for a specific count.
+    const DEFAULT_ITERATIONS: u32 = 120_000;
+
+    fn algorithm(&self) -> pbkdf2::Algorithm {
+        match self {
+            KdfParams::Pbkdf2Sha256 { .. } => PBKDF2_HMAC_SHA256,
+        }
+    }
+
+    fn iterations(&self) -> NonZeroU32 {
+        match self {
+            KdfParams::Pbkdf2Sha256 { iterations } => *iterations,
+        }
+    }
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        KdfParams::Pbkdf2Sha256 {
+            iterations: NonZeroU32::new(Self::DEFAULT_ITERATIONS).unwrap(),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Credential {
     password: Password,
     uuid: UUID,
+    kdf_params: KdfParams,
 }
 
 impl Credential {
     pub fn gen(rng: Arc<Mutex<dyn SecureRandom>>, uuid: &UUID) -> Self {
+        Self::gen_with_params(rng, uuid, KdfParams::default())
+    }
+
+    /// Like [`Credential::gen`], but with an explicit choice of [`KdfParams`]
+    /// rather than the default iteration count.
+    pub fn gen_with_params(
+        rng: Arc<Mutex<dyn SecureRandom>>,
+        uuid: &UUID,
+        kdf_params: KdfParams,
+    ) -> Self {
         let password = Password::gen(rng);
         Credential {
             password,
             uuid: uuid.clone(),
+            kdf_params,
+        }
+    }
+
+    /// Generates one credential per entry of `voters`, in parallel. Each item
+    /// seeds its own CSPRNG rather than contending on a single locked one, so
+    /// the expensive PBKDF2 step can run concurrently across the whole list.
+    pub fn gen_batch(voters: &[Voter_ID], uuid: &UUID) -> Vec<(Voter_ID, ExpandedCredential)> {
+        voters
+            .par_iter()
+            .map(|voter| {
+                let rng: Arc<Mutex<dyn SecureRandom>> =
+                    Arc::new(Mutex::new(ring::rand::SystemRandom::new()));
+                (voter.clone(), ExpandedCredential::gen(rng, uuid))
+            })
+            .collect()
+    }
+
+    /// Like [`Credential::gen_batch`], but every credential is derived
+    /// deterministically from `seed`, so a run can be reproduced and audited.
+    /// Each voter gets an independent sub-seed `H(seed || index)`, so the
+    /// batch is still safe to compute in parallel.
+    pub fn gen_batch_seeded(
+        seed: [u8; digest::SHA256_OUTPUT_LEN],
+        voters: &[Voter_ID],
+        uuid: &UUID,
+    ) -> Vec<(Voter_ID, ExpandedCredential)> {
+        voters
+            .par_iter()
+            .enumerate()
+            .map(|(index, voter)| {
+                let mut ctx = digest::Context::new(&digest::SHA256);
+                ctx.update(&seed);
+                ctx.update(&(index as u64).to_be_bytes());
+                let mut sub_seed = [0u8; digest::SHA256_OUTPUT_LEN];
+                sub_seed.copy_from_slice(ctx.finish().as_ref());
+
+                let rng: Arc<Mutex<dyn SecureRandom>> =
+                    Arc::new(Mutex::new(SeededRng::new(sub_seed)));
+                (voter.clone(), ExpandedCredential::gen(rng, uuid))
+            })
+            .collect()
+    }
+}
+
+/// A counter-mode CSPRNG seeded from a fixed master seed, used only to make
+/// [`Credential::gen_batch_seeded`] reproducible: the same sub-seed always
+/// yields the same byte stream.
+struct SeededRng {
+    seed: [u8; digest::SHA256_OUTPUT_LEN],
+    counter: std::cell::Cell<u64>,
+}
+
+impl SeededRng {
+    fn new(seed: [u8; digest::SHA256_OUTPUT_LEN]) -> Self {
+        SeededRng {
+            seed,
+            counter: std::cell::Cell::new(0),
+        }
+    }
+}
+
+impl SecureRandom for SeededRng {
+    fn fill(&self, dest: &mut [u8]) -> Result<(), ring::error::Unspecified> {
+        let mut offset = 0;
+        while offset < dest.len() {
+            let counter = self.counter.get();
+            self.counter.set(counter + 1);
+
+            let mut ctx = digest::Context::new(&digest::SHA256);
+            ctx.update(&self.seed);
+            ctx.update(&counter.to_be_bytes());
+            let block = ctx.finish();
+            let block = block.as_ref();
+
+            let take = (dest.len() - offset).min(block.len());
+            dest[offset..offset + take].copy_from_slice(&block[..take]);
+            offset += take;
         }
+        Ok(())
     }
 }
 
@@ -97,6 +233,17 @@ impl From<(Password, UUID)> for Credential {
         Self {
             password: pass,
             uuid,
+            kdf_params: KdfParams::default(),
+        }
+    }
+}
+
+impl From<(Password, UUID, KdfParams)> for Credential {
+    fn from((pass, uuid, kdf_params): (Password, UUID, KdfParams)) -> Self {
+        Self {
+            password: pass,
+            uuid,
+            kdf_params,
         }
     }
 }
@@ -105,6 +252,12 @@ impl From<(Password, UUID)> for Credential {
 pub struct ExpandedCredential {
     pub(crate) password: Password,
     pub(crate) uuid: UUID,
+    pub(crate) kdf_params: KdfParams,
+    /// The raw PBKDF2 output `secret_key` was reduced from. `secret_key` alone
+    /// can't be handed to [`Password::verify`]: reducing mod the group order is
+    /// lossy, so this is the only value `pbkdf2::verify` can check a password
+    /// against.
+    pub(crate) kdf_output: [u8; digest::SHA256_OUTPUT_LEN],
     pub(crate) secret_key: Scalar,
     pub(crate) public_key: Point,
 }
@@ -113,6 +266,13 @@ impl ExpandedCredential {
     pub fn gen(rng: Arc<Mutex<dyn SecureRandom>>, uuid: &UUID) -> Self {
         Credential::gen(rng, uuid).into()
     }
+
+    /// Signs `msg` with this credential's El Gamal secret key, binding the
+    /// resulting signature to `public_key` so a ballot can be authenticated
+    /// as coming from this voter.
+    pub fn sign(&self, rng: Arc<Mutex<dyn SecureRandom>>, msg: &[u8]) -> Signature {
+        signature::sign(rng, &self.secret_key, &self.public_key, msg)
+    }
 }
 
 impl From<Credential> for ExpandedCredential {
@@ -120,8 +280,8 @@ impl From<Credential> for ExpandedCredential {
         // I do not believe the hash used in PBKDF2 needs to be domain-separated,
         // it seems like only really the hashes in the ZKPs need to be.
         let mut out = [0; digest::SHA256_OUTPUT_LEN];
-        let algo = PBKDF2_HMAC_SHA256;
-        let iter = NonZeroU32::new(1000).unwrap();
+        let algo = c.kdf_params.algorithm();
+        let iter = c.kdf_params.iterations();
         let salt = (&c.uuid.0).into();
         let secret = (&c.password.0).into();
         pbkdf2::derive(algo, iter, salt, secret, &mut out);
@@ -131,6 +291,8 @@ impl From<Credential> for ExpandedCredential {
         ExpandedCredential {
             password: c.password,
             uuid: c.uuid,
+            kdf_params: c.kdf_params,
+            kdf_output: out,
             secret_key,
             public_key,
         }
@@ -142,6 +304,7 @@ impl From<ExpandedCredential> for Credential {
         Credential {
             uuid: expanded.uuid,
             password: expanded.password,
+            kdf_params: expanded.kdf_params,
         }
     }
 }
@@ -161,4 +324,37 @@ mod tests {
             assert!(cred.password.validate_checksum())
         }
     }
+
+    #[test]
+    fn test_kdf_params_roundtrip_and_migration() {
+        //! A credential derived with one `KdfParams` setting should still verify
+        //! and expand correctly, and an older (weaker) setting should still work.
+        let rng = Arc::new(Mutex::new(ring::rand::SystemRandom::new()));
+        let uuid = UUID::gen(rng.clone());
+
+        let legacy_params = KdfParams::Pbkdf2Sha256 {
+            iterations: NonZeroU32::new(1000).unwrap(),
+        };
+        let cred = Credential::gen_with_params(rng, &uuid, legacy_params);
+        let expanded: ExpandedCredential = cred.clone().into();
+
+        assert!(cred
+            .password
+            .verify(&uuid, &expanded.kdf_output, &cred.kdf_params));
+    }
+
+    #[test]
+    fn test_gen_batch_seeded_is_deterministic() {
+        let rng = Arc::new(Mutex::new(ring::rand::SystemRandom::new()));
+        let uuid = UUID::gen(rng);
+        let voters: Vec<Voter_ID> = (0..16).map(|i| Voter_ID::new(format!("voter-{i}"))).collect();
+        let seed = [42u8; digest::SHA256_OUTPUT_LEN];
+
+        let first_run = Credential::gen_batch_seeded(seed, &voters, &uuid);
+        let second_run = Credential::gen_batch_seeded(seed, &voters, &uuid);
+
+        for ((_, a), (_, b)) in first_run.iter().zip(second_run.iter()) {
+            assert_eq!(a.secret_key, b.secret_key);
+        }
+    }
 }
@@ -0,0 +1,172 @@
+//! Threshold trustee key generation.
+//!
+//! An election's secret key is never held by a single party: it is split across
+//! `n` trustees such that any `t` of them can reconstruct it, but any `t-1` learn
+//! nothing. Each trustee also acts as a dealer, running Feldman VSS: it samples a
+//! random degree-`(t-1)` polynomial over the scalar field, deals a share `f(j)` to
+//! every trustee `j`, and publishes commitments to its coefficients so a share can
+//! be checked against them without trusting the dealer. The election public key is
+//! the sum of every dealer's `a_0 * G`, and the shared secret is whatever any `t`
+//! trustees can reconstruct by Lagrange interpolation at `X = 0`.
+use crate::primitives::group::{Point, Scalar};
+use ring::rand::SecureRandom;
+use serde::{Deserialize, Serialize};
+use std::num::NonZeroU32;
+use std::sync::{Arc, Mutex};
+
+/// One trustee's share of a single dealer's polynomial.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Share {
+    /// The trustee index `j` (1-indexed; `j = 0` is reserved for the secret itself).
+    pub index: u32,
+    /// `f(j)`, the dealer's polynomial evaluated at this trustee's index.
+    pub value: Scalar,
+}
+
+/// Public commitments `C_0, ..., C_{t-1}` to a dealer's polynomial coefficients.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Commitments(Vec<Point>);
+
+impl Commitments {
+    /// This dealer's public contribution to the aggregate election key, `a_0 * G`.
+    pub fn public_contribution(&self) -> Point {
+        self.0[0]
+    }
+}
+
+/// A single dealer's run of Feldman VSS.
+///
+/// Samples a fresh degree-`(threshold - 1)` polynomial whose constant term is
+/// this dealer's contribution to the shared secret, then deals shares of it
+/// and publishes commitments to its coefficients.
+pub struct Dealer {
+    coefficients: Vec<Scalar>,
+}
+
+impl Dealer {
+    /// Samples the dealer's polynomial. `threshold` (the reconstruction
+    /// threshold `t`) must be at least 1, and should not exceed the number of
+    /// trustees `n` the caller intends to deal shares to, or no `t` of them
+    /// will ever be able to reconstruct the secret.
+    pub fn new(rng: Arc<Mutex<dyn SecureRandom>>, threshold: NonZeroU32) -> Self {
+        let coefficients = (0..threshold.get())
+            .map(|_| Self::random_scalar(&rng))
+            .collect();
+        Dealer { coefficients }
+    }
+
+    fn random_scalar(rng: &Arc<Mutex<dyn SecureRandom>>) -> Scalar {
+        let mut bytes = [0u8; 32];
+        rng.lock()
+            .unwrap()
+            .fill(&mut bytes)
+            .expect("failed to sample a polynomial coefficient");
+        Scalar::from_bytes_mod_order(bytes)
+    }
+
+    /// Evaluates `f(X) = a_0 + a_1 X + ... + a_{t-1} X^{t-1}` at `x`, via Horner's method.
+    fn evaluate(&self, x: u32) -> Scalar {
+        let x = Scalar::from(x as u64);
+        let mut acc = Scalar::zero();
+        for coefficient in self.coefficients.iter().rev() {
+            acc = acc * x + *coefficient;
+        }
+        acc
+    }
+
+    /// Deals this dealer's share to trustee `index` (1-indexed).
+    pub fn deal(&self, index: u32) -> Share {
+        Share {
+            index,
+            value: self.evaluate(index),
+        }
+    }
+
+    /// Publishes commitments `C_k = a_k * G` to every coefficient, so recipients
+    /// can verify their share without trusting the dealer.
+    pub fn commitments(&self) -> Commitments {
+        Commitments(
+            self.coefficients
+                .iter()
+                .map(|a| Point::generator() * *a)
+                .collect(),
+        )
+    }
+}
+
+/// Checks that `share` is consistent with `commitments`, i.e. that
+/// `f(j)*G == sum_k C_k * j^k`.
+pub fn verify_share(share: &Share, commitments: &Commitments) -> bool {
+    let lhs = Point::generator() * share.value;
+    let j = Scalar::from(share.index as u64);
+    let mut power = Scalar::one();
+    let mut rhs = Point::identity();
+    for commitment in &commitments.0 {
+        rhs = rhs + *commitment * power;
+        power = power * j;
+    }
+    lhs == rhs
+}
+
+/// Aggregates every dealer's public contribution into the election public key.
+pub fn aggregate_public_key(all_commitments: &[Commitments]) -> Point {
+    all_commitments
+        .iter()
+        .map(Commitments::public_contribution)
+        .fold(Point::identity(), |acc, c| acc + c)
+}
+
+/// Reconstructs the shared secret from `t` trustees' combined shares (each
+/// trustee's shares from every dealer, already summed), via Lagrange
+/// interpolation at `X = 0`: `secret = sum_j lambda_j * f(j)` with
+/// `lambda_j = prod_{m != j} m / (m - j)`.
+pub fn reconstruct_secret(shares: &[Share]) -> Scalar {
+    let mut secret = Scalar::zero();
+    for share in shares {
+        let j = Scalar::from(share.index as u64);
+        let mut lambda = Scalar::one();
+        for other in shares {
+            if other.index == share.index {
+                continue;
+            }
+            let m = Scalar::from(other.index as u64);
+            lambda = lambda * m * (m - j).invert();
+        }
+        secret = secret + lambda * share.value;
+    }
+    secret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_rng() -> Arc<Mutex<dyn SecureRandom>> {
+        Arc::new(Mutex::new(ring::rand::SystemRandom::new()))
+    }
+
+    #[test]
+    fn test_share_verifies_against_commitments() {
+        let dealer = Dealer::new(test_rng(), NonZeroU32::new(3).unwrap());
+        let commitments = dealer.commitments();
+        for index in 1..=5 {
+            let share = dealer.deal(index);
+            assert!(verify_share(&share, &commitments));
+        }
+    }
+
+    #[test]
+    fn test_threshold_reconstruction_recovers_secret() {
+        const N: u32 = 5;
+        const T: u32 = 3;
+        let dealer = Dealer::new(test_rng(), NonZeroU32::new(T).unwrap());
+        let secret = dealer.coefficients[0];
+
+        // Any T of the N shares should reconstruct the same secret.
+        let shares: Vec<Share> = (1..=T).map(|j| dealer.deal(j)).collect();
+        assert_eq!(reconstruct_secret(&shares), secret);
+
+        let other_shares: Vec<Share> = ((N - T + 1)..=N).map(|j| dealer.deal(j)).collect();
+        assert_eq!(reconstruct_secret(&other_shares), secret);
+    }
+}
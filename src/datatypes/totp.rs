@@ -0,0 +1,127 @@
+//! Time-based one-time passwords (RFC 6238), layered over a [`super::credentials::Credential`]
+//! as an optional second factor.
+//!
+//! A voter normally authenticates with just their PBKDF2-derived credential. An
+//! election can additionally require a `TotpSecret` enrolled alongside that
+//! credential: the voter's authenticator app and the server both compute
+//! `HOTP(K, T)` for the current time step `T = floor((now - T0) / step)`, where
+//! `HOTP` is HMAC-SHA1 dynamically truncated to a 6-digit code. Verification
+//! allows a `±1` step window to tolerate clock skew between the two.
+use ring::hmac;
+use ring::rand::SecureRandom;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+/// Length, in bytes, of a freshly enrolled shared secret.
+const SECRET_LEN: usize = 20;
+/// Number of digits in a generated code.
+const DIGITS: u32 = 6;
+/// RFC 6238's default time step.
+const DEFAULT_STEP_SECS: u64 = 30;
+/// How many steps on either side of the current one `verify` will accept,
+/// to tolerate clock skew between the voter's device and the server.
+const WINDOW: i64 = 1;
+
+/// A shared secret enrolled for a single credential, plus the time step it
+/// was enrolled with.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TotpSecret {
+    key: Vec<u8>,
+    step_secs: u64,
+}
+
+impl TotpSecret {
+    /// Enrols a fresh secret using the default 30s time step.
+    pub fn enrol(rng: Arc<Mutex<dyn SecureRandom>>) -> Self {
+        Self::enrol_with_step(rng, DEFAULT_STEP_SECS)
+    }
+
+    /// Like [`TotpSecret::enrol`], but with an explicit time step.
+    pub fn enrol_with_step(rng: Arc<Mutex<dyn SecureRandom>>, step_secs: u64) -> Self {
+        let mut key = vec![0u8; SECRET_LEN];
+        rng.lock()
+            .unwrap()
+            .fill(&mut key)
+            .expect("failed to sample a TOTP secret");
+        TotpSecret { key, step_secs }
+    }
+
+    /// Base32-encodes the shared secret, e.g. for a `otpauth://` provisioning
+    /// URI rendered as a QR code in an authenticator app.
+    pub fn to_base32(&self) -> String {
+        base32_encode(&self.key)
+    }
+
+    fn counter_at(&self, at_time: u64) -> u64 {
+        at_time / self.step_secs
+    }
+
+    /// `HOTP(K, counter)`: HMAC-SHA1 of the counter, dynamically truncated to
+    /// a `DIGITS`-digit code (RFC 4226).
+    fn hotp(&self, counter: u64) -> u32 {
+        let key = hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, &self.key);
+        let tag = hmac::sign(&key, &counter.to_be_bytes());
+        let digest = tag.as_ref();
+        let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+        let truncated = ((digest[offset] as u32 & 0x7f) << 24)
+            | ((digest[offset + 1] as u32) << 16)
+            | ((digest[offset + 2] as u32) << 8)
+            | (digest[offset + 3] as u32);
+        truncated % 10_u32.pow(DIGITS)
+    }
+
+    /// The code valid at `at_time`, mostly useful for enrolment checks and tests.
+    pub fn code_at(&self, at_time: u64) -> u32 {
+        self.hotp(self.counter_at(at_time))
+    }
+
+    /// Checks `code` against the codes valid around `at_time`, within a `±1`
+    /// step window to tolerate clock skew.
+    pub fn verify(&self, code: u32, at_time: u64) -> bool {
+        let counter = self.counter_at(at_time) as i64;
+        ((counter - WINDOW)..=(counter + WINDOW)).any(|c| c >= 0 && self.hotp(c as u64) == code)
+    }
+}
+
+fn base32_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+    let mut out = String::new();
+    let mut bits: u32 = 0;
+    let mut bit_count = 0u32;
+    for &byte in bytes {
+        bits = (bits << 8) | byte as u32;
+        bit_count += 8;
+        while bit_count >= 5 {
+            bit_count -= 5;
+            out.push(ALPHABET[((bits >> bit_count) & 0x1f) as usize] as char);
+        }
+    }
+    if bit_count > 0 {
+        out.push(ALPHABET[((bits << (5 - bit_count)) & 0x1f) as usize] as char);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_rng() -> Arc<Mutex<dyn SecureRandom>> {
+        Arc::new(Mutex::new(ring::rand::SystemRandom::new()))
+    }
+
+    #[test]
+    fn test_verify_accepts_current_code() {
+        let secret = TotpSecret::enrol(test_rng());
+        let code = secret.code_at(1_000_000);
+        assert!(secret.verify(code, 1_000_000));
+    }
+
+    #[test]
+    fn test_verify_tolerates_one_step_of_skew() {
+        let secret = TotpSecret::enrol(test_rng());
+        let code = secret.code_at(1_000_000);
+        assert!(secret.verify(code, 1_000_000 + DEFAULT_STEP_SECS));
+        assert!(!secret.verify(code, 1_000_000 + 3 * DEFAULT_STEP_SECS));
+    }
+}